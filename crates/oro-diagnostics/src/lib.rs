@@ -0,0 +1,17 @@
+/// Stable, documentation-linkable codes for the diagnostics orogene's
+/// various crates raise, so an error message can point a user at a page
+/// explaining it instead of just a one-liner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(non_camel_case_types)]
+pub enum DiagnosticCode {
+    /// A packument failed to deserialize.
+    OR1006,
+    /// A downloaded tarball's integrity check failed.
+    OR1010,
+}
+
+impl std::fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -0,0 +1,245 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Deserialize;
+use serde_json::Value;
+
+pub mod publish;
+
+/// Files npm always force-includes, no matter what `files` says.
+const ALWAYS_INCLUDE: &[&str] = &["package.json", "README", "LICENSE", "LICENCE"];
+
+/// Files/directories npm always force-excludes, even from a `files`
+/// allowlist or an explicit `.npmignore` re-include.
+const ALWAYS_EXCLUDE: &[&str] = &[
+    ".git",
+    ".svn",
+    ".hg",
+    "CVS",
+    ".npmrc",
+    ".lock-wscript",
+    ".DS_Store",
+    "npm-debug.log",
+    "config.gypi",
+    "node_modules",
+];
+
+#[derive(Debug, Deserialize, Default)]
+struct PackageJson {
+    name: Option<String>,
+    version: Option<String>,
+    main: Option<String>,
+    files: Option<Vec<String>>,
+    #[serde(alias = "bundleDependencies")]
+    #[serde(rename = "bundledDependencies")]
+    bundled_dependencies: Option<Vec<String>>,
+}
+
+/// Computes the set of files that belong in a package's published tarball,
+/// reproducing npm's own packing rules: a `files` allowlist (with npm's
+/// mandatory force-include/force-exclude sets layered on top), or, absent
+/// that, a full tree walk governed by `.npmignore` (falling back to
+/// `.gitignore`), plus any `bundledDependencies` pulled out of
+/// `node_modules`.
+#[derive(Debug, Default)]
+pub struct OroPack {
+    pkg: PackageJson,
+    raw: Value,
+}
+
+impl OroPack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads `package.json` out of the current directory.
+    pub fn load(&mut self) {
+        let data = fs::read_to_string("package.json").expect("Failed to read package.json");
+        self.pkg = serde_json::from_str(&data).expect("Failed to parse package.json");
+        self.raw = serde_json::from_str(&data).expect("Failed to parse package.json");
+    }
+
+    pub fn name(&self) -> &str {
+        self.pkg.name.as_deref().unwrap_or_default()
+    }
+
+    pub fn version(&self) -> &str {
+        self.pkg.version.as_deref().unwrap_or_default()
+    }
+
+    /// The parsed `package.json` contents, used as the basis for the
+    /// published version's metadata in the publish envelope.
+    pub fn raw_package_json(&self) -> &Value {
+        &self.raw
+    }
+
+    /// Returns the resolved set of paths, relative to the current
+    /// directory, that should be packed into the tarball.
+    pub fn project_paths(&self) -> Vec<PathBuf> {
+        let mut paths = match &self.pkg.files {
+            Some(files) => self.allowlisted_paths(files),
+            None => self.ignored_paths(),
+        };
+        self.add_bundled_dependencies(&mut paths);
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// When `files` is present in `package.json`, only entries matching it
+    /// are packed, plus the handful of files npm always force-includes
+    /// (`package.json`, `README*`, `LICENSE*`/`LICENCE*`, the `main`
+    /// entry), and minus the set it always force-excludes even if `files`
+    /// lists them explicitly. A `files` entry can name a whole top-level
+    /// directory (`"src"`) or a path nested inside one (`"dist/index.js"`),
+    /// so the walk has to descend into directories that could plausibly
+    /// contain a listed path, not just the top level.
+    fn allowlisted_paths(&self, files: &[String]) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        collect_allowlisted(Path::new("."), files, true, &mut paths);
+        if let Some(main) = &self.pkg.main {
+            let main_path = Path::new(main);
+            if main_path.exists() && !is_always_excluded(&file_name(main_path)) {
+                paths.push(main_path.to_path_buf());
+            }
+        }
+        paths
+    }
+
+    /// With no `files` allowlist, the whole tree is packed, filtered
+    /// through the project root's `.npmignore` (or `.gitignore` if that's
+    /// all there is): later patterns override earlier ones, and `!pattern`
+    /// re-includes -- except for the always-excluded set, which nothing can
+    /// re-include. Only the root ignore file is read; `.npmignore` and
+    /// `.gitignore` files in subdirectories are not consulted.
+    fn ignored_paths(&self) -> Vec<PathBuf> {
+        let mut builder = GitignoreBuilder::new(".");
+        let ignore_file = if Path::new(".npmignore").exists() {
+            Some(".npmignore")
+        } else if Path::new(".gitignore").exists() {
+            Some(".gitignore")
+        } else {
+            None
+        };
+        if let Some(ignore_file) = ignore_file {
+            builder.add(ignore_file);
+        }
+        let matcher = builder.build().expect("Failed to build ignore matcher");
+        let mut paths = Vec::new();
+        collect_matched(Path::new("."), &matcher, &mut paths);
+        paths
+    }
+
+    /// `bundledDependencies` are pulled in from `node_modules`, even though
+    /// `node_modules` is otherwise always excluded. Inside each bundled
+    /// dependency the always-excluded set still applies, so its own nested
+    /// `node_modules` is left out.
+    fn add_bundled_dependencies(&self, paths: &mut Vec<PathBuf>) {
+        for dep in self.pkg.bundled_dependencies.iter().flatten() {
+            let dep_path = Path::new("node_modules").join(dep);
+            if dep_path.exists() {
+                collect_all(&dep_path, paths);
+            }
+        }
+    }
+}
+
+fn collect_matched(dir: &Path, matcher: &Gitignore, paths: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("Failed to read directory") {
+        let entry = entry.expect("Failed to read directory entry");
+        let path = entry.path();
+        let name = file_name(&path);
+        let is_dir = path.is_dir();
+        if is_always_excluded(&name) {
+            continue;
+        }
+        if matcher.matched(&path, is_dir).is_ignore() {
+            continue;
+        }
+        if is_dir {
+            collect_matched(&path, matcher, paths);
+        } else {
+            paths.push(strip_prefix(&path));
+        }
+    }
+}
+
+/// Walks `dir` looking for paths that match a `files` allowlist entry,
+/// recursing into subdirectories that could contain a nested match (e.g.
+/// `"dist/index.js"` requires descending into `dist`) even when the
+/// directory itself isn't listed. `top_level` gates npm's force-include
+/// set, which only ever applies to files directly at the project root.
+fn collect_allowlisted(dir: &Path, files: &[String], top_level: bool, paths: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("Failed to read directory") {
+        let entry = entry.expect("Failed to read directory entry");
+        let path = entry.path();
+        let name = file_name(&path);
+        if is_always_excluded(&name) {
+            continue;
+        }
+        let rel = strip_prefix(&path);
+        let rel_str = rel.to_string_lossy();
+        let force_included =
+            top_level && ALWAYS_INCLUDE.iter().any(|prefix| name.starts_with(prefix));
+        let listed = files.iter().any(|f| matches_files_entry(f, &rel_str));
+        if force_included || listed {
+            if path.is_dir() {
+                collect_all(&path, paths);
+            } else {
+                paths.push(rel);
+            }
+            continue;
+        }
+        if path.is_dir() && files.iter().any(|f| is_potential_ancestor(f, &rel_str)) {
+            collect_allowlisted(&path, files, false, paths);
+        }
+    }
+}
+
+/// Whether a `files` entry matches a given relative path: either exactly,
+/// or because the entry names a directory the path lives under.
+fn matches_files_entry(entry: &str, rel: &str) -> bool {
+    entry == rel || rel.starts_with(&format!("{}/", entry))
+}
+
+/// Whether a `files` entry could still match something nested under `rel`,
+/// i.e. `rel` is a directory on the way to the entry's path.
+fn is_potential_ancestor(entry: &str, rel: &str) -> bool {
+    entry.starts_with(&format!("{}/", rel))
+}
+
+/// Collects every file under `dir` (used for a `files` entry or a
+/// `bundledDependencies` entry that names a whole directory), still
+/// filtering out the always-excluded set -- a `files: ["src"]` allowlist
+/// doesn't get to ship `src/.git` or `src/npm-debug.log` any more than the
+/// unfiltered tree walk does.
+fn collect_all(dir: &Path, paths: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("Failed to read directory") {
+        let entry = entry.expect("Failed to read directory entry");
+        let path = entry.path();
+        if is_always_excluded(&file_name(&path)) {
+            continue;
+        }
+        if path.is_dir() {
+            collect_all(&path, paths);
+        } else {
+            paths.push(strip_prefix(&path));
+        }
+    }
+}
+
+fn is_always_excluded(name: &str) -> bool {
+    ALWAYS_EXCLUDE.contains(&name) || name.ends_with(".orig") || name.starts_with(".wafpickle-")
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn strip_prefix(path: &Path) -> PathBuf {
+    path.strip_prefix(".").unwrap_or(path).to_path_buf()
+}
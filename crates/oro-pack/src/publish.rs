@@ -0,0 +1,240 @@
+use std::io;
+use std::path::Path;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use http_types::Method;
+use oro_client::OroClient;
+use serde_json::{json, Value};
+use sha1::Sha1;
+use sha2::{Digest, Sha512};
+
+use crate::OroPack;
+
+/// Everything [`publish`] needs besides the package itself: where to push
+/// the tarball, which `dist-tag` the published version gets, and whatever
+/// credentials that registry requires.
+#[derive(Debug, Clone)]
+pub struct PublishOpts {
+    pub registry: url::Url,
+    pub tag: String,
+    /// Pre-built `Authorization` header value, as resolved from
+    /// `.npmrc`-style auth config for the target registry.
+    pub auth_header: Option<String>,
+    /// Stop after building the envelope instead of sending it, printing the
+    /// file manifest instead -- analogous to cargo's `--dry-run`.
+    pub dry_run: bool,
+}
+
+/// The result of packing a project up for publish: the manifest of files
+/// that went in, the gzipped tarball itself, and its two flavors of
+/// checksum.
+pub struct PackedTarball {
+    pub files: Vec<std::path::PathBuf>,
+    pub bytes: Vec<u8>,
+    pub integrity: String,
+    pub shasum: String,
+}
+
+/// Packs `pack`'s project files into an npm-layout tarball (everything
+/// under a `package/` prefix) and computes its integrity metadata.
+pub fn pack_tarball(pack: &OroPack) -> io::Result<PackedTarball> {
+    let files = pack.project_paths();
+    let mut bytes = Vec::new();
+    {
+        let enc = GzEncoder::new(&mut bytes, Compression::default());
+        let mut tar = tar::Builder::new(enc);
+        for file in &files {
+            tar.append_path_with_name(file, Path::new("package").join(file))?;
+        }
+        let enc = tar.into_inner()?;
+        enc.finish()?;
+    }
+    let shasum = {
+        let mut hasher = Sha1::new();
+        Digest::update(&mut hasher, &bytes);
+        hex::encode(Digest::finalize(hasher))
+    };
+    let integrity = {
+        let mut hasher = Sha512::new();
+        Digest::update(&mut hasher, &bytes);
+        format!("sha512-{}", base64::encode(Digest::finalize(hasher)))
+    };
+    Ok(PackedTarball {
+        files,
+        bytes,
+        integrity,
+        shasum,
+    })
+}
+
+/// Builds the npm publish JSON envelope: the published version's metadata
+/// (the project's own `package.json`, plus the computed `dist` block),
+/// the `_attachments` entry carrying the base64-encoded tarball, and the
+/// `dist-tags` pointing the given tag at this version.
+pub fn build_envelope(pack: &OroPack, tarball: &PackedTarball, tag: &str) -> Value {
+    let name = pack.name();
+    let version = pack.version();
+    let tarball_name = format!("{}-{}.tgz", name, version);
+
+    let mut version_meta = pack.raw_package_json().clone();
+    if let Value::Object(ref mut map) = version_meta {
+        map.insert(
+            "dist".to_string(),
+            json!({
+                "integrity": tarball.integrity,
+                "shasum": tarball.shasum,
+                "tarball": tarball_name,
+            }),
+        );
+    }
+
+    json!({
+        "_id": name,
+        "name": name,
+        "description": version_meta.get("description").cloned().unwrap_or(Value::Null),
+        "dist-tags": { tag: version },
+        "versions": { version: version_meta },
+        "_attachments": {
+            tarball_name: {
+                "content_type": "application/octet-stream",
+                "data": base64::encode(&tarball.bytes),
+                "length": tarball.bytes.len(),
+            }
+        },
+    })
+}
+
+/// Packs, builds the envelope for, and (unless `opts.dry_run`) publishes
+/// the project at `pack` by issuing an authenticated `PUT` to
+/// `{registry}/{name}`.
+pub async fn publish(client: &OroClient, pack: &OroPack, opts: &PublishOpts) -> io::Result<Value> {
+    let tarball = pack_tarball(pack)?;
+    let envelope = build_envelope(pack, &tarball, &opts.tag);
+
+    if opts.dry_run {
+        for file in &tarball.files {
+            println!("{}", file.display());
+        }
+        return Ok(envelope);
+    }
+
+    let url = opts
+        .registry
+        .join(pack.name())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut req = client.opts(Method::Put, url).body_json(&envelope).map_err(|e| {
+        io::Error::new(io::ErrorKind::Other, e.to_string())
+    })?;
+    if let Some(auth_header) = &opts.auth_header {
+        req = req.header("Authorization", auth_header.as_str());
+    }
+    let mut res = client
+        .send(req)
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+    if !res.status().is_success() {
+        let body = res
+            .body_string()
+            .await
+            .unwrap_or_else(|_| "<no response body>".to_string());
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!(
+                "Registry rejected publish of {} with {}: {}",
+                pack.name(),
+                res.status(),
+                body
+            ),
+        ));
+    }
+    Ok(envelope)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::File;
+    use std::io::Write as _;
+    use std::sync::Mutex;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    // `OroPack::load` reads `package.json` out of the process's current
+    // directory, so these tests can't run concurrently with each other
+    // without racing on `env::set_current_dir`.
+    static CWD_LOCK: Mutex<()> = Mutex::new(());
+
+    fn lock_cwd() -> std::sync::MutexGuard<'static, ()> {
+        CWD_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn loaded_pack() -> OroPack {
+        File::create("package.json")
+            .unwrap()
+            .write_all(br#"{ "name": "testpackage", "version": "1.2.3" }"#)
+            .unwrap();
+        let mut pack = OroPack::new();
+        pack.load();
+        pack
+    }
+
+    #[test]
+    fn build_envelope_fills_in_dist_and_attachments() -> std::io::Result<()> {
+        let _guard = lock_cwd();
+        let cwd = env::current_dir()?;
+        let dir = tempdir()?;
+        env::set_current_dir(dir.path())?;
+
+        let pack = loaded_pack();
+        let tarball = PackedTarball {
+            files: vec![],
+            bytes: b"totally a tarball".to_vec(),
+            integrity: "sha512-AAAA".to_string(),
+            shasum: "deadbeef".to_string(),
+        };
+
+        let envelope = build_envelope(&pack, &tarball, "latest");
+
+        let version_meta = &envelope["versions"]["1.2.3"];
+        assert_eq!(version_meta["dist"]["integrity"], "sha512-AAAA");
+        assert_eq!(version_meta["dist"]["shasum"], "deadbeef");
+        assert_eq!(version_meta["dist"]["tarball"], "testpackage-1.2.3.tgz");
+        assert_eq!(envelope["dist-tags"]["latest"], "1.2.3");
+
+        let attachment = &envelope["_attachments"]["testpackage-1.2.3.tgz"];
+        assert_eq!(attachment["length"], tarball.bytes.len());
+        assert_eq!(attachment["data"], base64::encode(&tarball.bytes));
+
+        env::set_current_dir(cwd)?;
+        Ok(())
+    }
+
+    #[test]
+    fn pack_tarball_reports_the_files_it_packed() -> std::io::Result<()> {
+        let _guard = lock_cwd();
+        let cwd = env::current_dir()?;
+        let dir = tempdir()?;
+        env::set_current_dir(dir.path())?;
+
+        let pack = loaded_pack();
+        File::create("index.js")?;
+
+        let tarball = pack_tarball(&pack)?;
+        assert!(tarball.files.contains(&std::path::PathBuf::from("package.json")));
+        assert!(tarball.files.contains(&std::path::PathBuf::from("index.js")));
+        assert!(tarball.integrity.starts_with("sha512-"));
+        assert_eq!(tarball.shasum.len(), 40);
+
+        env::set_current_dir(cwd)?;
+        Ok(())
+    }
+
+    // `publish`'s non-2xx handling is exercised by sending a request through
+    // a real `OroClient`/registry connection, which these tests can't stand
+    // up without a mock HTTP server this crate doesn't depend on. The pure
+    // halves it delegates to -- `pack_tarball` and `build_envelope` -- are
+    // covered above instead.
+}
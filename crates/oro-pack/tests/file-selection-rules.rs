@@ -0,0 +1,164 @@
+use fs::File;
+use oro_pack::*;
+use std::env;
+use std::io::Write as _;
+use std::sync::Mutex;
+use std::{fs, path::PathBuf};
+use tempfile::tempdir;
+
+// `OroPack` operates on the process's current directory, which is global
+// state -- these tests must not run concurrently with each other or they'll
+// stomp on each other's `env::set_current_dir`.
+static CWD_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_cwd() -> std::sync::MutexGuard<'static, ()> {
+    CWD_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[test]
+fn files_allowlist_still_force_excludes_junk() -> std::io::Result<()> {
+    let _guard = lock_cwd();
+    let cwd = env::current_dir()?;
+    let dir = tempdir()?;
+    env::set_current_dir(dir.path())?;
+
+    File::create("package.json")?
+        .write_all(br#"{ "name": "testpackage", "files": ["src"] }"#)?;
+    fs::create_dir_all("src")?;
+    File::create("src/index.js")?;
+    File::create("src/.DS_Store")?;
+    File::create("src/npm-debug.log")?;
+
+    let mut pack = OroPack::new();
+    pack.load();
+    let paths = pack.project_paths();
+
+    assert!(paths.contains(&PathBuf::from("src/index.js")));
+    assert!(paths.contains(&PathBuf::from("package.json")));
+    assert!(!paths.contains(&PathBuf::from("src/.DS_Store")));
+    assert!(!paths.contains(&PathBuf::from("src/npm-debug.log")));
+
+    env::set_current_dir(cwd)?;
+    Ok(())
+}
+
+#[test]
+fn npmignore_is_preferred_over_gitignore() -> std::io::Result<()> {
+    let _guard = lock_cwd();
+    let cwd = env::current_dir()?;
+    let dir = tempdir()?;
+    env::set_current_dir(dir.path())?;
+
+    File::create("package.json")?.write_all(br#"{ "name": "testpackage" }"#)?;
+    File::create(".gitignore")?.write_all(b"*.js\n")?;
+    File::create(".npmignore")?.write_all(b"*.md\n")?;
+    File::create("index.js")?;
+    File::create("notes.md")?;
+
+    let mut pack = OroPack::new();
+    pack.load();
+    let paths = pack.project_paths();
+
+    // .npmignore wins over .gitignore: *.md is excluded, *.js (only
+    // matched by .gitignore) is kept.
+    assert!(paths.contains(&PathBuf::from("index.js")));
+    assert!(!paths.contains(&PathBuf::from("notes.md")));
+
+    env::set_current_dir(cwd)?;
+    Ok(())
+}
+
+#[test]
+fn npmignore_negation_reincludes_files() -> std::io::Result<()> {
+    let _guard = lock_cwd();
+    let cwd = env::current_dir()?;
+    let dir = tempdir()?;
+    env::set_current_dir(dir.path())?;
+
+    File::create("package.json")?.write_all(br#"{ "name": "testpackage" }"#)?;
+    File::create(".npmignore")?.write_all(b"*.log\n!keep.log\n")?;
+    File::create("debug.log")?;
+    File::create("keep.log")?;
+
+    let mut pack = OroPack::new();
+    pack.load();
+    let paths = pack.project_paths();
+
+    assert!(!paths.contains(&PathBuf::from("debug.log")));
+    assert!(paths.contains(&PathBuf::from("keep.log")));
+
+    env::set_current_dir(cwd)?;
+    Ok(())
+}
+
+#[test]
+fn always_excluded_set_cannot_be_reincluded_by_npmignore() -> std::io::Result<()> {
+    let _guard = lock_cwd();
+    let cwd = env::current_dir()?;
+    let dir = tempdir()?;
+    env::set_current_dir(dir.path())?;
+
+    File::create("package.json")?.write_all(br#"{ "name": "testpackage" }"#)?;
+    File::create(".npmignore")?.write_all(b"!.git\n")?;
+    fs::create_dir_all(".git")?;
+    File::create(".git/config")?;
+
+    let mut pack = OroPack::new();
+    pack.load();
+    let paths = pack.project_paths();
+
+    assert!(!paths.iter().any(|p| p.starts_with(".git")));
+
+    env::set_current_dir(cwd)?;
+    Ok(())
+}
+
+#[test]
+fn bundled_dependencies_are_pulled_from_node_modules() -> std::io::Result<()> {
+    let _guard = lock_cwd();
+    let cwd = env::current_dir()?;
+    let dir = tempdir()?;
+    env::set_current_dir(dir.path())?;
+
+    File::create("package.json")?.write_all(
+        br#"{ "name": "testpackage", "bundledDependencies": ["some-dep"] }"#,
+    )?;
+    fs::create_dir_all("node_modules/some-dep")?;
+    File::create("node_modules/some-dep/index.js")?;
+    fs::create_dir_all("node_modules/other-dep")?;
+    File::create("node_modules/other-dep/index.js")?;
+
+    let mut pack = OroPack::new();
+    pack.load();
+    let paths = pack.project_paths();
+
+    assert!(paths.contains(&PathBuf::from("node_modules/some-dep/index.js")));
+    assert!(!paths.contains(&PathBuf::from("node_modules/other-dep/index.js")));
+
+    env::set_current_dir(cwd)?;
+    Ok(())
+}
+
+#[test]
+fn files_entry_matches_a_nested_path() -> std::io::Result<()> {
+    let _guard = lock_cwd();
+    let cwd = env::current_dir()?;
+    let dir = tempdir()?;
+    env::set_current_dir(dir.path())?;
+
+    File::create("package.json")?
+        .write_all(br#"{ "name": "testpackage", "files": ["dist/index.js"] }"#)?;
+    fs::create_dir_all("dist")?;
+    File::create("dist/index.js")?;
+    File::create("dist/index.js.map")?;
+
+    let mut pack = OroPack::new();
+    pack.load();
+    let paths = pack.project_paths();
+
+    assert!(paths.contains(&PathBuf::from("dist/index.js")));
+    assert!(!paths.contains(&PathBuf::from("dist/index.js.map")));
+
+    env::set_current_dir(cwd)?;
+    Ok(())
+}
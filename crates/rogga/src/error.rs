@@ -0,0 +1,52 @@
+use oro_diagnostics::DiagnosticCode;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}")]
+    MiscError(String),
+
+    #[error("Failed to parse packument for {name} ({code})")]
+    SerdeError {
+        code: DiagnosticCode,
+        name: String,
+        data: String,
+        #[source]
+        serde_error: serde_json::Error,
+    },
+
+    #[error("Integrity check failed for {algorithm} digest ({code}): expected {expected}, got {actual}")]
+    IntegrityMismatch {
+        code: DiagnosticCode,
+        algorithm: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("{context}")]
+    Context {
+        context: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Lets any `Result` whose error implements `std::error::Error` be given a
+/// human-readable bit of context and folded into our own `Error` type, the
+/// same way `anyhow::Context` works.
+pub trait Internal<T> {
+    fn with_context<F: FnOnce() -> String>(self, context: F) -> Result<T>;
+}
+
+impl<T, E> Internal<T> for std::result::Result<T, E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn with_context<F: FnOnce() -> String>(self, context: F) -> Result<T> {
+        self.map_err(|source| Error::Context {
+            context: context(),
+            source: Box::new(source),
+        })
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use base64::encode as base64_encode;
+use url::Url;
+
+/// Credentials for a single registry, as parsed out of `.npmrc`. Mirrors the
+/// handful of auth shapes npm itself supports: a bearer token, a
+/// pre-encoded `_auth` basic string, or a loose `username`/`_password` pair.
+#[derive(Debug, Clone)]
+pub enum Credentials {
+    AuthToken(String),
+    Basic { username: String, password: String },
+    BasicAuth(String),
+}
+
+impl Credentials {
+    /// Renders the `Authorization` header value this credential produces.
+    pub fn to_header(&self) -> String {
+        match self {
+            Credentials::AuthToken(token) => format!("Bearer {}", token),
+            Credentials::Basic { username, password } => {
+                format!("Basic {}", base64_encode(format!("{}:{}", username, password)))
+            }
+            Credentials::BasicAuth(auth) => format!("Basic {}", auth),
+        }
+    }
+}
+
+/// `.npmrc`-style auth config, keyed by the registry URL prefix the
+/// credentials apply to (e.g. `//registry.example.com/` or
+/// `//registry.example.com/some-scope/`). Lookups match the longest
+/// registered prefix so path-scoped registries are handled correctly, and a
+/// credential is only ever sent to the host it was configured for.
+#[derive(Debug, Clone, Default)]
+pub struct AuthConfig {
+    entries: HashMap<String, Credentials>,
+}
+
+impl AuthConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses `.npmrc`-style lines such as:
+    ///
+    /// ```text
+    /// //registry.example.com/:_authToken=some-token
+    /// //registry.example.com/:_auth=dXNlcjpwYXNz
+    /// //registry.example.com/:username=user
+    /// //registry.example.com/:_password=cGFzcw==
+    /// ```
+    pub fn parse(input: &str) -> Self {
+        let mut entries: HashMap<String, Credentials> = HashMap::new();
+        let mut pending_basic: HashMap<String, (Option<String>, Option<String>)> = HashMap::new();
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some(pair) => pair,
+                None => continue,
+            };
+            let key = key.trim();
+            let value = value.trim().trim_matches('"');
+            if !key.starts_with("//") {
+                continue;
+            }
+            let (prefix, field) = match key.rsplit_once(":_") {
+                Some((prefix, field)) => (prefix, field),
+                None => match key.rsplit_once(":") {
+                    Some((prefix, field)) => (prefix, field),
+                    None => continue,
+                },
+            };
+            match field {
+                "authToken" => {
+                    entries.insert(prefix.to_string(), Credentials::AuthToken(value.to_string()));
+                }
+                "auth" => {
+                    entries.insert(prefix.to_string(), Credentials::BasicAuth(value.to_string()));
+                }
+                "password" => {
+                    // `_password` is base64-encoded in `.npmrc`; a value that
+                    // doesn't decode leaves the pair incomplete.
+                    let password = base64::decode(value).ok().and_then(|b| String::from_utf8(b).ok());
+                    pending_basic.entry(prefix.to_string()).or_default().1 = password;
+                }
+                "username" => {
+                    pending_basic.entry(prefix.to_string()).or_default().0 = Some(value.to_string());
+                }
+                _ => {}
+            }
+        }
+        for (prefix, (username, password)) in pending_basic {
+            if let (Some(username), Some(password)) = (username, password) {
+                entries
+                    .entry(prefix)
+                    .or_insert(Credentials::Basic { username, password });
+            }
+        }
+        Self { entries }
+    }
+
+    /// Finds the credentials whose registered prefix best matches `url`,
+    /// preferring the longest (most specific) match so a scope-specific
+    /// `.npmrc` entry wins over a host-wide one.
+    ///
+    /// The port is included in the match target when `url` specifies a
+    /// non-default one (e.g. a local Verdaccio on `:4873`), so `.npmrc`
+    /// entries keyed to a specific port only ever match that port, and
+    /// entries with no port (the common case for the default registry)
+    /// never accidentally match a request to some other port on the same
+    /// host.
+    pub fn credentials_for(&self, url: &Url) -> Option<&Credentials> {
+        let host = url.host_str()?;
+        let target = match url.port() {
+            Some(port) => format!("//{}:{}{}", host, port, url.path()),
+            None => format!("//{}{}", host, url.path()),
+        };
+        self.entries
+            .iter()
+            .filter(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, creds)| creds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_auth_token() {
+        let config = AuthConfig::parse("//registry.example.com/:_authToken=some-token\n");
+        let url = Url::parse("https://registry.example.com/left-pad").unwrap();
+        assert_eq!(
+            config.credentials_for(&url).unwrap().to_header(),
+            "Bearer some-token"
+        );
+    }
+
+    #[test]
+    fn parses_basic_auth() {
+        let config = AuthConfig::parse("//registry.example.com/:_auth=dXNlcjpwYXNz\n");
+        let url = Url::parse("https://registry.example.com/left-pad").unwrap();
+        assert_eq!(
+            config.credentials_for(&url).unwrap().to_header(),
+            "Basic dXNlcjpwYXNz"
+        );
+    }
+
+    #[test]
+    fn parses_username_and_password_pair() {
+        let config = AuthConfig::parse(
+            "//registry.example.com/:username=user\n//registry.example.com/:_password=cGFzcw==\n",
+        );
+        let url = Url::parse("https://registry.example.com/left-pad").unwrap();
+        assert_eq!(
+            config.credentials_for(&url).unwrap().to_header(),
+            "Basic dXNlcjpwYXNz"
+        );
+    }
+
+    #[test]
+    fn prefers_longest_matching_prefix() {
+        let config = AuthConfig::parse(
+            "//registry.example.com/:_authToken=host-wide\n//registry.example.com/@myorg/:_authToken=scoped\n",
+        );
+        let url = Url::parse("https://registry.example.com/@myorg/some-pkg").unwrap();
+        assert_eq!(
+            config.credentials_for(&url).unwrap().to_header(),
+            "Bearer scoped"
+        );
+    }
+
+    #[test]
+    fn matches_port_qualified_registry() {
+        let config = AuthConfig::parse("//localhost:4873/:_authToken=local-token\n");
+        let url = Url::parse("http://localhost:4873/left-pad").unwrap();
+        assert_eq!(
+            config.credentials_for(&url).unwrap().to_header(),
+            "Bearer local-token"
+        );
+    }
+
+    #[test]
+    fn does_not_leak_credentials_to_a_different_port() {
+        let config = AuthConfig::parse("//localhost:4873/:_authToken=local-token\n");
+        let url = Url::parse("http://localhost:9999/left-pad").unwrap();
+        assert!(config.credentials_for(&url).is_none());
+    }
+
+    #[test]
+    fn portless_entry_does_not_match_a_specific_port() {
+        let config = AuthConfig::parse("//localhost/:_authToken=default-token\n");
+        let url = Url::parse("http://localhost:4873/left-pad").unwrap();
+        assert!(config.credentials_for(&url).is_none());
+    }
+
+    #[test]
+    fn does_not_leak_credentials_to_a_different_host() {
+        let config = AuthConfig::parse("//registry.example.com/:_authToken=some-token\n");
+        let url = Url::parse("https://evil.example.com/left-pad").unwrap();
+        assert!(config.credentials_for(&url).is_none());
+    }
+}
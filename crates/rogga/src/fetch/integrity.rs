@@ -0,0 +1,238 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::io::AsyncRead;
+use futures::ready;
+use oro_diagnostics::DiagnosticCode;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::error::Error;
+use crate::packument::Dist;
+
+/// Wraps an `AsyncRead` tarball stream and verifies its contents against
+/// the `integrity`/`shasum` fields of a package's `dist` metadata as the
+/// bytes go by, failing the read with an `Error` on EOF if the digests
+/// don't match. This makes tampered or corrupted downloads loud instead of
+/// silently installable.
+pub struct IntegrityChecker<R> {
+    inner: R,
+    expected: Option<Expected>,
+    hasher: Option<Hasher>,
+    done: bool,
+}
+
+impl<R: AsyncRead + Unpin> IntegrityChecker<R> {
+    pub fn new(inner: R, dist: &Dist) -> Self {
+        // `integrity` (SSRI) takes priority over the legacy `shasum` field
+        // when both are present, same as npm itself does.
+        let expected = dist
+            .integrity
+            .as_deref()
+            .and_then(parse_ssri)
+            .map(|(algorithm, digest)| Expected::Integrity(algorithm, digest))
+            .or_else(|| dist.shasum.clone().map(Expected::Shasum));
+        let hasher = expected.as_ref().map(|expected| match expected {
+            Expected::Integrity(Algorithm::Sha512, _) => Hasher::Sha512(Sha512::new()),
+            Expected::Integrity(Algorithm::Sha256, _) => Hasher::Sha256(Sha256::new()),
+            Expected::Integrity(Algorithm::Sha1, _) | Expected::Shasum(_) => {
+                Hasher::Sha1(Sha1::new())
+            }
+        });
+        Self {
+            inner,
+            expected,
+            hasher,
+            done: false,
+        }
+    }
+
+    fn verify(&mut self) -> io::Result<()> {
+        let (expected, hasher) = match (self.expected.take(), self.hasher.take()) {
+            (Some(expected), Some(hasher)) => (expected, hasher),
+            // No integrity info was published for this version. Nothing to
+            // check; let it through as we always have in the past.
+            _ => return Ok(()),
+        };
+        let actual = hasher.finalize();
+        if digest_matches(&expected, &actual) {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                Error::IntegrityMismatch {
+                    code: DiagnosticCode::OR1010,
+                    algorithm: expected.algorithm_name().into(),
+                    expected: expected.to_string(),
+                    actual: hex::encode(&actual),
+                },
+            ))
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for IntegrityChecker<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let n = ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        if n == 0 {
+            if !this.done {
+                this.done = true;
+                this.verify()?;
+            }
+            return Poll::Ready(Ok(0));
+        }
+        if let Some(hasher) = this.hasher.as_mut() {
+            hasher.update(&buf[..n]);
+        }
+        Poll::Ready(Ok(n))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    Sha512,
+    Sha256,
+    Sha1,
+}
+
+impl Algorithm {
+    fn priority(self) -> u8 {
+        match self {
+            Algorithm::Sha512 => 2,
+            Algorithm::Sha256 => 1,
+            Algorithm::Sha1 => 0,
+        }
+    }
+
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "sha512" => Some(Algorithm::Sha512),
+            "sha256" => Some(Algorithm::Sha256),
+            "sha1" => Some(Algorithm::Sha1),
+            _ => None,
+        }
+    }
+}
+
+enum Expected {
+    Integrity(Algorithm, Vec<u8>),
+    Shasum(String),
+}
+
+impl Expected {
+    fn algorithm_name(&self) -> &'static str {
+        match self {
+            Expected::Integrity(Algorithm::Sha512, _) => "sha512",
+            Expected::Integrity(Algorithm::Sha256, _) => "sha256",
+            Expected::Integrity(Algorithm::Sha1, _) => "sha1",
+            Expected::Shasum(_) => "sha1",
+        }
+    }
+}
+
+impl std::fmt::Display for Expected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expected::Integrity(_, bytes) => write!(f, "{}", base64::encode(bytes)),
+            Expected::Shasum(hex) => write!(f, "{}", hex),
+        }
+    }
+}
+
+enum Hasher {
+    Sha512(Sha512),
+    Sha256(Sha256),
+    Sha1(Sha1),
+}
+
+impl Hasher {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha512(h) => Digest::update(h, data),
+            Hasher::Sha256(h) => Digest::update(h, data),
+            Hasher::Sha1(h) => Digest::update(h, data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Sha512(h) => Digest::finalize(h).to_vec(),
+            Hasher::Sha256(h) => Digest::finalize(h).to_vec(),
+            Hasher::Sha1(h) => Digest::finalize(h).to_vec(),
+        }
+    }
+}
+
+/// Parses an SSRI string such as `sha512-<base64> sha1-<base64>` into the
+/// single strongest `(algorithm, digest)` pair present, since npm allows
+/// multiple space-separated hashes and we only need to check one.
+fn parse_ssri(integrity: &str) -> Option<(Algorithm, Vec<u8>)> {
+    integrity
+        .split_whitespace()
+        .filter_map(|entry| {
+            let (prefix, b64) = entry.split_once('-')?;
+            let algorithm = Algorithm::from_prefix(prefix)?;
+            let digest = base64::decode(b64).ok()?;
+            Some((algorithm, digest))
+        })
+        .max_by_key(|(algorithm, _)| algorithm.priority())
+}
+
+/// Whether a computed digest matches what was expected, comparing raw
+/// bytes for SSRI integrity and case-insensitive hex for the legacy
+/// `shasum` field.
+fn digest_matches(expected: &Expected, actual: &[u8]) -> bool {
+    match expected {
+        Expected::Integrity(_, expected_bytes) => actual == expected_bytes.as_slice(),
+        Expected::Shasum(expected_hex) => hex::encode(actual).eq_ignore_ascii_case(expected_hex),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ssri_picks_single_entry() {
+        let (algorithm, digest) = parse_ssri("sha512-AAAA").unwrap();
+        assert_eq!(algorithm, Algorithm::Sha512);
+        assert_eq!(digest, base64::decode("AAAA").unwrap());
+    }
+
+    #[test]
+    fn parse_ssri_prefers_strongest_algorithm() {
+        let (algorithm, _) = parse_ssri("sha1-AAAA sha512-BBBB sha256-CCCC").unwrap();
+        assert_eq!(algorithm, Algorithm::Sha512);
+    }
+
+    #[test]
+    fn parse_ssri_rejects_unknown_algorithm() {
+        assert_eq!(parse_ssri("md5-AAAA"), None);
+    }
+
+    #[test]
+    fn parse_ssri_rejects_malformed_entry() {
+        assert_eq!(parse_ssri("not-valid-base64-!!!"), None);
+        assert_eq!(parse_ssri(""), None);
+    }
+
+    #[test]
+    fn digest_matches_integrity_requires_exact_bytes() {
+        let expected = Expected::Integrity(Algorithm::Sha256, vec![1, 2, 3]);
+        assert!(digest_matches(&expected, &[1, 2, 3]));
+        assert!(!digest_matches(&expected, &[1, 2, 4]));
+    }
+
+    #[test]
+    fn digest_matches_shasum_is_case_insensitive_hex() {
+        let expected = Expected::Shasum("AABBCC".into());
+        assert!(digest_matches(&expected, &[0xaa, 0xbb, 0xcc]));
+        assert!(!digest_matches(&expected, &[0xaa, 0xbb, 0xcd]));
+    }
+}
@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures::AsyncReadExt;
+use tide::{Body, Request, Response, StatusCode};
+
+use crate::fetch::cache::BlobCache;
+use crate::fetch::npm::NpmFetcher;
+
+#[derive(Clone)]
+struct MirrorState {
+    fetcher: Arc<NpmFetcher>,
+    tarballs: BlobCache,
+}
+
+/// A lazily-populating mirror for a single upstream registry: packument
+/// requests fall through to `NpmFetcher`'s own on-disk cache and tarball
+/// requests to the mirror's own tarball cache (each, on a miss, making a
+/// real upstream request), so only packages teams have
+/// actually installed ever get written to disk. Tarballs are verified
+/// against the upstream `dist` metadata as they're ingested, so the mirror
+/// never persists a corrupt artifact, and packuments revalidate against
+/// the upstream `ETag` the same way a direct install would.
+pub struct MirrorServer {
+    app: tide::Server<MirrorState>,
+}
+
+impl MirrorServer {
+    pub fn new(fetcher: Arc<NpmFetcher>, tarball_cache_dir: impl Into<PathBuf>) -> Self {
+        let state = MirrorState {
+            fetcher,
+            tarballs: BlobCache::new(tarball_cache_dir),
+        };
+        let mut app = tide::with_state(state);
+        app.at("/:name").get(get_packument);
+        app.at("/:name/-/:filename").get(get_tarball);
+        // Scoped packages (`@scope/name`) live at a two-segment path, same
+        // as the real npm registry -- the single-segment routes above never
+        // match them.
+        app.at("/:scope/:name").get(get_scoped_packument);
+        app.at("/:scope/:name/-/:filename").get(get_scoped_tarball);
+        Self { app }
+    }
+
+    pub async fn listen(self, addr: impl tide::listener::ToListener<MirrorState>) -> std::io::Result<()> {
+        self.app.listen(addr).await
+    }
+}
+
+async fn get_packument(req: Request<MirrorState>) -> tide::Result {
+    let name = req.param("name")?.to_string();
+    packument_response(req.state(), &name).await
+}
+
+async fn get_scoped_packument(req: Request<MirrorState>) -> tide::Result {
+    let name = scoped_name(&req)?;
+    packument_response(req.state(), &name).await
+}
+
+async fn get_tarball(req: Request<MirrorState>) -> tide::Result {
+    let name = req.param("name")?.to_string();
+    let filename = req.param("filename")?.to_string();
+    tarball_response(req.state(), &name, &filename).await
+}
+
+async fn get_scoped_tarball(req: Request<MirrorState>) -> tide::Result {
+    let name = scoped_name(&req)?;
+    let filename = req.param("filename")?.to_string();
+    tarball_response(req.state(), &name, &filename).await
+}
+
+/// Recombines the `:scope`/`:name` route params of a scoped route back
+/// into the `@scope/name` form `NpmFetcher` (and `split_scope`) expect.
+fn scoped_name(req: &Request<MirrorState>) -> tide::Result<String> {
+    let scope = req.param("scope")?.to_string();
+    let name = req.param("name")?.to_string();
+    Ok(format!("{}/{}", scope, name))
+}
+
+async fn packument_response(state: &MirrorState, name: &str) -> tide::Result {
+    match state.fetcher.packument_for_name(name).await {
+        Ok(packument) => Ok(Response::builder(StatusCode::Ok)
+            .body(Body::from_json(&packument)?)
+            .build()),
+        Err(err) => Ok(Response::builder(StatusCode::BadGateway)
+            .body(err.to_string())
+            .build()),
+    }
+}
+
+async fn tarball_response(state: &MirrorState, name: &str, filename: &str) -> tide::Result {
+    // The tarball cache is keyed by name + filename, so any request for a
+    // tarball that's already been mirrored is served straight off disk
+    // without touching the upstream registry at all.
+    let cache_key: url::Url = format!("mirror:///{}/-/{}", name, filename)
+        .parse()
+        .expect("mirror cache keys are always valid URLs");
+    if let Some(cached) = state.tarballs.get(&cache_key).await {
+        return Ok(Response::builder(StatusCode::Ok).body(cached).build());
+    }
+
+    let mut stream = match state.fetcher.fetch_and_verify_tarball(name, filename).await {
+        Ok(stream) => stream,
+        Err(err) => {
+            return Ok(Response::builder(StatusCode::BadGateway)
+                .body(err.to_string())
+                .build())
+        }
+    };
+    let mut bytes = Vec::new();
+    stream.read_to_end(&mut bytes).await?;
+    state
+        .tarballs
+        .store(&cache_key, &bytes)
+        .await
+        .map_err(|err| tide::Error::from_str(StatusCode::InternalServerError, err.to_string()))?;
+    Ok(Response::builder(StatusCode::Ok).body(bytes).build())
+}
@@ -1,17 +1,20 @@
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use async_std::sync::{Arc, Mutex};
 use async_trait::async_trait;
 use dashmap::DashMap;
 use futures::io::AsyncRead;
-use http_types::Method;
+use http_types::{Method, StatusCode};
 use oro_client::{self, OroClient};
 use oro_diagnostics::DiagnosticCode;
 use oro_package_spec::PackageSpec;
 use url::Url;
 
 use crate::error::{Error, Internal, Result};
+use crate::fetch::auth::AuthConfig;
+use crate::fetch::cache::{CacheEntry, CacheMode, PackumentCache};
+use crate::fetch::integrity::IntegrityChecker;
 use crate::fetch::PackageFetcher;
 use crate::package::Package;
 use crate::packument::{Packument, VersionMetadata};
@@ -27,6 +30,9 @@ pub struct NpmFetcher {
     use_corgi: bool,
     registries: HashMap<String, Url>,
     packuments: DashMap<Url, Packument>,
+    cache: PackumentCache,
+    cache_mode: CacheMode,
+    auth: AuthConfig,
 }
 
 impl NpmFetcher {
@@ -34,17 +40,61 @@ impl NpmFetcher {
         client: Arc<Mutex<OroClient>>,
         use_corgi: bool,
         registries: HashMap<String, Url>,
+        cache_dir: PathBuf,
+        cache_mode: CacheMode,
+        auth: AuthConfig,
     ) -> Self {
         Self {
             client,
             use_corgi,
             registries,
             packuments: DashMap::new(),
+            cache: PackumentCache::new(cache_dir),
+            cache_mode,
+            auth,
         }
     }
 }
 
 impl NpmFetcher {
+    /// Looks up a packument by package name (scoped or not), going through
+    /// the same cache and network path as a normal install. Exposed so
+    /// that [`MirrorServer`](crate::fetch::mirror::MirrorServer) can
+    /// answer packument requests without needing a full `PackageSpec`.
+    pub async fn packument_for_name(&self, name: &str) -> Result<Packument> {
+        let (scope, name) = split_scope(name);
+        self.packument_from_name(&scope, name).await
+    }
+
+    /// Fetches and integrity-verifies the tarball for `name`'s version
+    /// whose `dist.tarball` ends in `filename`, as published by the
+    /// registry. Used by the mirror server to serve (and cache) the exact
+    /// tarball a client asked for by its npm-style URL.
+    pub async fn fetch_and_verify_tarball(
+        &self,
+        name: &str,
+        filename: &str,
+    ) -> Result<Box<dyn AsyncRead + Unpin + Send + Sync>> {
+        let packument = self.packument_for_name(name).await?;
+        let metadata = packument
+            .versions
+            .values()
+            .find(|v| v.dist.tarball.as_str().ends_with(filename))
+            .cloned()
+            .ok_or_else(|| Error::MiscError(format!("No version of {} matches {}.", name, filename)))?;
+
+        let client = self.client.lock().await.clone();
+        let mut opts = client.opts(Method::Get, metadata.dist.tarball.clone());
+        if let Some(creds) = self.auth.credentials_for(&metadata.dist.tarball) {
+            opts = opts.header("Authorization", creds.to_header());
+        }
+        let stream = client
+            .send(opts)
+            .await
+            .with_context(|| format!("Failed to get tarball for {}.", name))?;
+        Ok(Box::new(IntegrityChecker::new(stream, &metadata.dist)))
+    }
+
     fn pick_registry(&self, scope: &Option<String>) -> Url {
         if let Some(scope) = scope {
             self.registries
@@ -63,7 +113,6 @@ impl NpmFetcher {
     }
 
     async fn packument_from_name(&self, scope: &Option<String>, name: &str) -> Result<Packument> {
-        let client = self.client.lock().await.clone();
         let packument_url = self
             .pick_registry(scope)
             .join(&name)
@@ -71,33 +120,102 @@ impl NpmFetcher {
         if let Some(packument) = self.packuments.get(&packument_url) {
             return Ok(packument.value().clone());
         }
-        let opts = client.opts(Method::Get, packument_url.clone());
-        let packument_data = client
-            .send(opts.header(
-                "Accept",
-                if self.use_corgi {
-                    "application/vnd.npm.install-v1+json; q=1.0, application/json; q=0.8, */*"
-                } else {
-                    "application/json"
-                },
-            ))
-            .await
-            .with_context(|| format!("Failed to get packument for {}.", name))?
-            .body_string()
+
+        let cached = if self.cache_mode == CacheMode::ReloadAll {
+            None
+        } else {
+            self.cache.get(&packument_url).await
+        };
+
+        if self.cache_mode == CacheMode::OnlyIfCached {
+            let cached = cached.ok_or_else(|| Error::MiscError(format!(
+                "{} is not in the cache and --offline was specified.",
+                name
+            )))?;
+            let packument = cached.packument()?;
+            self.packuments.insert(packument_url, packument.clone());
+            return Ok(packument);
+        }
+
+        let client = self.client.lock().await.clone();
+        let mut opts = client.opts(Method::Get, packument_url.clone()).header(
+            "Accept",
+            if self.use_corgi {
+                "application/vnd.npm.install-v1+json; q=1.0, application/json; q=0.8, */*"
+            } else {
+                "application/json"
+            },
+        );
+        if let Some(creds) = self.auth.credentials_for(&packument_url) {
+            opts = opts.header("Authorization", creds.to_header());
+        }
+        if self.cache_mode != CacheMode::NoCache {
+            if let Some(cached) = &cached {
+                if let Some(etag) = &cached.etag {
+                    opts = opts.header("If-None-Match", etag.as_str());
+                }
+                if let Some(last_modified) = &cached.last_modified {
+                    opts = opts.header("If-Modified-Since", last_modified.as_str());
+                }
+            }
+        }
+
+        let mut res = client
+            .send(opts)
             .await
-            .map_err(|e| Error::MiscError(e.to_string()))?;
+            .with_context(|| format!("Failed to get packument for {}.", name))?;
+
+        let (packument_data, etag, last_modified) = if res.status() == StatusCode::NotModified {
+            let cached = cached.ok_or_else(|| {
+                Error::MiscError(format!("Registry sent 304 for uncached packument {}.", name))
+            })?;
+            (cached.body, cached.etag, cached.last_modified)
+        } else {
+            let etag = res.header("ETag").map(|v| v.as_str().to_string());
+            let last_modified = res.header("Last-Modified").map(|v| v.as_str().to_string());
+            let body = res
+                .body_string()
+                .await
+                .map_err(|e| Error::MiscError(e.to_string()))?;
+            (body, etag, last_modified)
+        };
+
         let packument: Packument =
             serde_json::from_str(&packument_data).map_err(|err| Error::SerdeError {
                 code: DiagnosticCode::OR1006,
                 name: name.into(),
-                data: packument_data,
+                data: packument_data.clone(),
                 serde_error: err,
             })?;
+
+        if self.cache_mode != CacheMode::NoCache {
+            self.cache
+                .store(
+                    &packument_url,
+                    &CacheEntry {
+                        etag,
+                        last_modified,
+                        body: packument_data,
+                    },
+                )
+                .await?;
+        }
+
         self.packuments.insert(packument_url, packument.clone());
         Ok(packument)
     }
 }
 
+/// Splits a package name like `@scope/name` into its scope (kept with its
+/// leading `@`, matching how `.npmrc`/`registries` key scopes) and the bare
+/// package name. Unscoped names are returned as-is with no scope.
+fn split_scope(name: &str) -> (Option<String>, &str) {
+    match name.strip_prefix('@').and_then(|rest| rest.split_once('/')) {
+        Some((scope, rest)) => (Some(format!("@{}", scope)), rest),
+        None => (None, name),
+    }
+}
+
 #[async_trait]
 impl PackageFetcher for NpmFetcher {
     async fn name(&self, spec: &PackageSpec, _base_dir: &Path) -> Result<String> {
@@ -149,11 +267,18 @@ impl PackageFetcher for NpmFetcher {
             PackageResolution::Npm { ref tarball, .. } => tarball,
             _ => panic!("How did a non-Npm resolution get here?"),
         };
-        Ok(Box::new(
-            client
-                .send(client.opts(Method::Get, url.clone()))
-                .await
-                .with_context(|| format!("Failed to get tarball for {:#?}.", pkg.resolved))?,
-        ))
+        let mut opts = client.opts(Method::Get, url.clone());
+        if let Some(creds) = self.auth.credentials_for(url) {
+            opts = opts.header("Authorization", creds.to_header());
+        }
+        let stream = client
+            .send(opts)
+            .await
+            .with_context(|| format!("Failed to get tarball for {:#?}.", pkg.resolved))?;
+        // Verify the tarball against `dist.integrity`/`dist.shasum` as it's
+        // streamed, so a corrupted or tampered download fails loudly instead
+        // of silently getting extracted onto disk.
+        let metadata = self.metadata(pkg).await?;
+        Ok(Box::new(IntegrityChecker::new(stream, &metadata.dist)))
     }
 }
\ No newline at end of file